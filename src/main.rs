@@ -1,8 +1,16 @@
+mod remote_filter;
+mod resolver;
+mod watch;
+
 use anyhow::Result;
 use clap::Parser;
 use netstat2::{
     AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, TcpState, iterate_sockets_info,
 };
+use serde::Serialize;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::IsTerminal;
 use std::io::Write;
 use std::net::IpAddr;
 use std::path::PathBuf;
@@ -11,10 +19,11 @@ use tabwriter::TabWriter;
 use users::get_user_by_uid;
 
 /// Simple process info structure.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 struct ProcessInfo {
     pid: u32,
     uid: Option<u32>,
+    user: String,
     name: String,
     cmd: Vec<String>,
     exe: PathBuf,
@@ -22,18 +31,58 @@ struct ProcessInfo {
 }
 
 /// Our unified socket structure.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 struct SocketInfo {
     local_port: u16,
     local_addr: IpAddr,
     remote_port: Option<u16>,
     remote_addr: Option<IpAddr>,
+    #[serde(serialize_with = "serialize_protocol")]
     protocol: ProtocolFlags,
+    #[serde(serialize_with = "serialize_state")]
     state: Option<TcpState>,
+    #[serde(serialize_with = "serialize_family")]
     family: AddressFamilyFlags,
     processes: Vec<ProcessInfo>,
 }
 
+/// Renders `protocol` as "tcp"/"udp" for JSON output, since `ProtocolFlags`
+/// doesn't derive `Serialize`.
+fn serialize_protocol<S>(protocol: &ProtocolFlags, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let s = if *protocol == ProtocolFlags::UDP {
+        "udp"
+    } else {
+        "tcp"
+    };
+    serializer.serialize_str(s)
+}
+
+/// Renders `family` as "ipv4"/"ipv6" for JSON output, since
+/// `AddressFamilyFlags` doesn't derive `Serialize`.
+fn serialize_family<S>(family: &AddressFamilyFlags, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let s = if *family == AddressFamilyFlags::IPV6 {
+        "ipv6"
+    } else {
+        "ipv4"
+    };
+    serializer.serialize_str(s)
+}
+
+/// Renders `state` using the same strings the table view shows, since
+/// `TcpState` doesn't derive `Serialize`.
+fn serialize_state<S>(state: &Option<TcpState>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(tcp_state_to_str(state))
+}
+
 /// Retrieves sockets for a given address family.
 fn get_sockets(sys: &System, addr: AddressFamilyFlags) -> Result<Vec<SocketInfo>> {
     let protos = ProtocolFlags::TCP | ProtocolFlags::UDP;
@@ -56,6 +105,7 @@ fn get_sockets(sys: &System, addr: AddressFamilyFlags) -> Result<Vec<SocketInfo>
                 if let Some(process) = sys.process(pid_obj) {
                     let name = process.name().to_string_lossy().into_owned();
                     let uid = process.user_id().map(|uid_ref| **uid_ref);
+                    let user = resolve_user(uid);
                     let cmd = process
                         .cmd()
                         .iter()
@@ -66,6 +116,7 @@ fn get_sockets(sys: &System, addr: AddressFamilyFlags) -> Result<Vec<SocketInfo>
                     ProcessInfo {
                         pid,
                         uid,
+                        user,
                         name,
                         cmd,
                         exe,
@@ -76,6 +127,7 @@ fn get_sockets(sys: &System, addr: AddressFamilyFlags) -> Result<Vec<SocketInfo>
                     ProcessInfo {
                         pid,
                         uid: None,
+                        user: resolve_user(None),
                         name,
                         cmd: Vec::new(),
                         exe: PathBuf::new(),
@@ -126,7 +178,8 @@ fn format_command_line(cmd: &[String], max_length: usize) -> String {
     }
 }
 
-/// Convert TCP state to string representation
+/// Convert TCP state to string representation. UDP sockets carry no state,
+/// so they render as "-" rather than "UNKNOWN".
 fn tcp_state_to_str(state: &Option<TcpState>) -> &'static str {
     match state {
         Some(TcpState::Listen) => "LISTEN",
@@ -141,36 +194,76 @@ fn tcp_state_to_str(state: &Option<TcpState>) -> &'static str {
         Some(TcpState::TimeWait) => "TIME_WAIT",
         Some(TcpState::Closed) => "CLOSED",
         Some(TcpState::DeleteTcb) => "DELETE_TCB",
-        Some(TcpState::Unknown) | None => "UNKNOWN",
+        Some(TcpState::Unknown) => "UNKNOWN",
+        None => "-",
     }
 }
 
-/// Get protocol string based on address family
-fn get_protocol_string(family: AddressFamilyFlags) -> &'static str {
-    match family {
-        AddressFamilyFlags::IPV4 => "TCP",
-        AddressFamilyFlags::IPV6 => "TCP6",
+/// Get protocol string based on protocol and address family
+fn get_protocol_string(protocol: ProtocolFlags, family: AddressFamilyFlags) -> &'static str {
+    match (protocol, family) {
+        (ProtocolFlags::UDP, AddressFamilyFlags::IPV6) => "UDP6",
+        (ProtocolFlags::UDP, _) => "UDP",
+        (_, AddressFamilyFlags::IPV6) => "TCP6",
         _ => "TCP",
     }
 }
 
-/// Format remote address for display
-fn format_remote_address(remote_addr: Option<IpAddr>, remote_port: Option<u16>) -> String {
+/// Format remote address for display, substituting a resolved hostname for
+/// the numeric IP when one is available in `resolved`.
+fn format_remote_address(
+    remote_addr: Option<IpAddr>,
+    remote_port: Option<u16>,
+    resolved: Option<&HashMap<IpAddr, Option<String>>>,
+) -> String {
     if let (Some(raddr), Some(rport)) = (remote_addr, remote_port) {
-        format!("{raddr}:{rport}")
+        let host = resolved
+            .and_then(|cache| cache.get(&raddr))
+            .and_then(Option::clone)
+            .unwrap_or_else(|| raddr.to_string());
+        format!("{host}:{rport}")
     } else {
         "-".to_string()
     }
 }
 
-/// Get user information from UID
-fn get_user_info(uid: Option<u32>) -> (String, String) {
-    let uid_str = uid.map_or_else(|| "unknown".to_string(), |uid| uid.to_string());
-    let user = uid
-        .and_then(get_user_by_uid)
+/// Format a UID for display, independent of whether it resolves to a user name.
+fn format_uid(uid: Option<u32>) -> String {
+    uid.map_or_else(|| "unknown".to_string(), |uid| uid.to_string())
+}
+
+/// Resolve a UID to its user name, for stashing on `ProcessInfo` once
+/// rather than re-looking it up at every print site.
+fn resolve_user(uid: Option<u32>) -> String {
+    uid.and_then(get_user_by_uid)
         .map(|u| u.name().to_string_lossy().into_owned())
-        .unwrap_or_else(|| "unknown".to_string());
-    (uid_str, user)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Which protocols to include in the listing.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum ProtocolFilter {
+    Tcp,
+    Udp,
+    All,
+}
+
+/// Output format for the socket listing.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Table,
+    Json,
+    Ndjson,
+}
+
+impl ProtocolFilter {
+    fn matches(self, protocol: ProtocolFlags) -> bool {
+        match self {
+            ProtocolFilter::Tcp => protocol == ProtocolFlags::TCP,
+            ProtocolFilter::Udp => protocol == ProtocolFlags::UDP,
+            ProtocolFilter::All => true,
+        }
+    }
 }
 
 /// Command-line arguments.
@@ -184,11 +277,48 @@ struct Args {
     /// Show detailed process information in table view
     #[arg(short, long)]
     detailed: bool,
+
+    /// Which protocols to list
+    #[arg(long, value_enum, default_value_t = ProtocolFilter::All)]
+    protocol: ProtocolFilter,
+
+    /// Skip reverse-DNS resolution of remote addresses and show numeric IPs.
+    /// Resolution is already off by default when stdout isn't a terminal
+    /// (e.g. piped into another command), since nothing is there to read it.
+    #[arg(long)]
+    no_resolve: bool,
+
+    /// Continuously monitor per-process bandwidth instead of a one-shot snapshot
+    #[arg(long)]
+    watch: bool,
+
+    /// Network interface to capture on when using --watch (defaults to the first active one)
+    #[arg(long)]
+    interface: Option<String>,
+
+    /// How often to refresh the --watch table, in seconds
+    #[arg(long, default_value_t = 1)]
+    refresh_interval: u64,
+
+    /// Only show sockets talking to this host, IP, or CIDR (e.g. 10.0.0.0/8)
+    #[arg(long)]
+    remote: Option<String>,
+
+    /// Output format: a tab-aligned table, pretty JSON, or one JSON object per line
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    if args.watch {
+        return watch::run(
+            args.interface.as_deref(),
+            std::time::Duration::from_secs(args.refresh_interval),
+        );
+    }
+
     // Refresh process information.
     let mut sys = System::new_all();
     sys.refresh_processes(ProcessesToUpdate::All, true);
@@ -198,15 +328,61 @@ fn main() -> Result<()> {
     let mut sockets6 = get_sockets(&sys, AddressFamilyFlags::IPV6)?;
     sockets.append(&mut sockets6);
 
-    // Focus on TCP sockets only.
-    let mut tcp_sockets: Vec<SocketInfo> = sockets
+    // Filter sockets down to the requested protocol(s).
+    let mut sockets_filtered: Vec<SocketInfo> = sockets
         .into_iter()
-        .filter(|s| s.protocol == ProtocolFlags::TCP)
+        .filter(|s| args.protocol.matches(s.protocol))
         .collect();
 
+    if let Some(remote_spec) = &args.remote {
+        let filter = remote_filter::parse(remote_spec)?;
+        sockets_filtered.retain(|s| s.remote_addr.is_some_and(|addr| filter.contains(addr)));
+    }
+
+    if args.output != OutputFormat::Table {
+        let mut out_sockets = sockets_filtered;
+        if let Some(filter_port) = args.port {
+            out_sockets.retain(|s| s.local_port == filter_port);
+            if out_sockets.is_empty() {
+                return Err(anyhow::anyhow!("Port {} is not in use.", filter_port));
+            }
+        }
+
+        match args.output {
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&out_sockets)?);
+            }
+            OutputFormat::Ndjson => {
+                for s in &out_sockets {
+                    println!("{}", serde_json::to_string(s)?);
+                }
+            }
+            OutputFormat::Table => unreachable!("guarded by the outer if"),
+        }
+
+        return Ok(());
+    }
+
+    // Reverse-resolve each unique remote address once and reuse the result
+    // everywhere that address appears. Default to resolving only when
+    // stdout is a terminal, since a piped/scripted invocation has no one
+    // around to read hostnames and shouldn't pay the lookup latency.
+    let no_resolve = args.no_resolve || !std::io::stdout().is_terminal();
+    let resolved: Option<HashMap<IpAddr, Option<String>>> = if no_resolve {
+        None
+    } else {
+        let unique_addrs: Vec<IpAddr> = sockets_filtered
+            .iter()
+            .filter_map(|s| s.remote_addr)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        Some(resolver::resolve_addrs(&unique_addrs))
+    };
+
     if let Some(filter_port) = args.port {
         // Filter for matching sockets.
-        let matching: Vec<&SocketInfo> = tcp_sockets
+        let matching: Vec<&SocketInfo> = sockets_filtered
             .iter()
             .filter(|s| s.local_port == filter_port)
             .collect();
@@ -216,16 +392,17 @@ fn main() -> Result<()> {
         } else {
             // Detailed print for each matching socket.
             for s in matching {
-                let proto_str = get_protocol_string(s.family);
+                let proto_str = get_protocol_string(s.protocol, s.family);
                 let state_str = tcp_state_to_str(&s.state);
 
                 // Use the first associated process (if any).
-                let (pid, proc_name, uid_opt, cmd, exe, cwd) =
+                let (pid, proc_name, uid_opt, user, cmd, exe, cwd) =
                     if let Some(proc_info) = s.processes.first() {
                         (
                             proc_info.pid,
                             proc_info.name.clone(),
                             proc_info.uid,
+                            proc_info.user.clone(),
                             &proc_info.cmd,
                             &proc_info.exe,
                             &proc_info.cwd,
@@ -235,16 +412,17 @@ fn main() -> Result<()> {
                             0,
                             "unknown".to_string(),
                             None,
+                            "unknown".to_string(),
                             &Vec::new(),
                             &PathBuf::new(),
                             &PathBuf::new(),
                         )
                     };
 
-                let (uid_str, user) = get_user_info(uid_opt);
+                let uid_str = format_uid(uid_opt);
 
                 let local = format!("{}:{}", s.local_addr, s.local_port);
-                let remote = format_remote_address(s.remote_addr, s.remote_port);
+                let remote = format_remote_address(s.remote_addr, s.remote_port, resolved.as_ref());
 
                 println!("Port {}/{}:", s.local_port, proto_str);
                 println!("  Local Address: {local}");
@@ -278,10 +456,10 @@ fn main() -> Result<()> {
             }
         }
     } else {
-        // Sort tcp_sockets in descending order by port.
-        tcp_sockets.sort_by(|a, b| a.local_port.cmp(&b.local_port));
+        // Sort sockets_filtered in ascending order by port.
+        sockets_filtered.sort_by(|a, b| a.local_port.cmp(&b.local_port));
 
-        // Print a table of all TCP sockets.
+        // Print a table of all matching sockets.
         let mut tw = TabWriter::new(std::io::stdout());
 
         if args.detailed {
@@ -291,25 +469,28 @@ fn main() -> Result<()> {
                 "PORT\tPID\tUID\tUSER\tSTATUS\tPROTOCOL\tPROCESS_NAME\tCOMMAND\tLOCAL\tREMOTE"
             )
             .map_err(|e| anyhow::anyhow!("Failed to write to output: {}", e))?;
-            for s in tcp_sockets {
-                let proto_str = get_protocol_string(s.family);
+            for s in sockets_filtered {
+                let proto_str = get_protocol_string(s.protocol, s.family);
                 let state_str = tcp_state_to_str(&s.state);
 
-                let (pid, proc_name, uid_opt, cmd) = if let Some(proc_info) = s.processes.first() {
+                let (pid, proc_name, uid_opt, user, cmd) = if let Some(proc_info) =
+                    s.processes.first()
+                {
                     (
                         proc_info.pid,
                         proc_info.name.clone(),
                         proc_info.uid,
+                        proc_info.user.clone(),
                         &proc_info.cmd,
                     )
                 } else {
-                    (0, "unknown".to_string(), None, &Vec::new())
+                    (0, "unknown".to_string(), None, "unknown".to_string(), &Vec::new())
                 };
 
-                let (uid_str, user) = get_user_info(uid_opt);
+                let uid_str = format_uid(uid_opt);
 
                 let local = format!("{}:{}", s.local_addr, s.local_port);
-                let remote = format_remote_address(s.remote_addr, s.remote_port);
+                let remote = format_remote_address(s.remote_addr, s.remote_port, resolved.as_ref());
 
                 let command_display = format_command_line(cmd, 40);
 
@@ -336,20 +517,26 @@ fn main() -> Result<()> {
                 "PORT\tPID\tUID\tUSER\tSTATUS\tPROTOCOL\tPROCESS_NAME\tLOCAL\tREMOTE"
             )
             .map_err(|e| anyhow::anyhow!("Failed to write to output: {}", e))?;
-            for s in tcp_sockets {
-                let proto_str = get_protocol_string(s.family);
+            for s in sockets_filtered {
+                let proto_str = get_protocol_string(s.protocol, s.family);
                 let state_str = tcp_state_to_str(&s.state);
 
-                let (pid, proc_name, uid_opt) = if let Some(proc_info) = s.processes.first() {
-                    (proc_info.pid, proc_info.name.clone(), proc_info.uid)
+                let (pid, proc_name, uid_opt, user) = if let Some(proc_info) = s.processes.first()
+                {
+                    (
+                        proc_info.pid,
+                        proc_info.name.clone(),
+                        proc_info.uid,
+                        proc_info.user.clone(),
+                    )
                 } else {
-                    (0, "unknown".to_string(), None)
+                    (0, "unknown".to_string(), None, "unknown".to_string())
                 };
 
-                let (uid_str, user) = get_user_info(uid_opt);
+                let uid_str = format_uid(uid_opt);
 
                 let local = format!("{}:{}", s.local_addr, s.local_port);
-                let remote = format_remote_address(s.remote_addr, s.remote_port);
+                let remote = format_remote_address(s.remote_addr, s.remote_port, resolved.as_ref());
 
                 writeln!(
                     tw,
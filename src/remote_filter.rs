@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::net::{IpAddr, ToSocketAddrs};
+
+/// Filters sockets by remote peer: a literal IP, a resolved hostname (every
+/// A/AAAA record it has), or a CIDR block.
+pub enum RemoteFilter {
+    Addrs(HashSet<IpAddr>),
+    Cidr { network: IpAddr, prefix_len: u8 },
+}
+
+impl RemoteFilter {
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match self {
+            RemoteFilter::Addrs(addrs) => addrs.contains(&addr),
+            RemoteFilter::Cidr {
+                network,
+                prefix_len,
+            } => cidr_contains(*network, *prefix_len, addr),
+        }
+    }
+}
+
+/// Parses `--remote`'s argument: a CIDR block (`10.0.0.0/8`, `fe80::/10`),
+/// a literal IP, or a hostname, which is resolved up front to the full set
+/// of addresses it answers to.
+pub fn parse(spec: &str) -> Result<RemoteFilter> {
+    if let Some((network, prefix_len)) = spec.split_once('/') {
+        let network: IpAddr = network
+            .parse()
+            .with_context(|| format!("Invalid CIDR network: {spec}"))?;
+        let prefix_len: u8 = prefix_len
+            .parse()
+            .with_context(|| format!("Invalid CIDR prefix length: {spec}"))?;
+        let max_prefix_len = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_prefix_len {
+            anyhow::bail!("Invalid CIDR prefix length: {spec}");
+        }
+        return Ok(RemoteFilter::Cidr {
+            network,
+            prefix_len,
+        });
+    }
+
+    if let Ok(addr) = spec.parse::<IpAddr>() {
+        return Ok(RemoteFilter::Addrs(HashSet::from([addr])));
+    }
+
+    // Not an IP or CIDR, so treat it as a hostname and resolve every
+    // address it has up front, mirroring resolver::resolve_addrs' approach
+    // of doing the lookup once rather than per matched socket.
+    let addrs: HashSet<IpAddr> = (spec, 0u16)
+        .to_socket_addrs()
+        .with_context(|| format!("Failed to resolve host: {spec}"))?
+        .map(|socket_addr| socket_addr.ip())
+        .collect();
+
+    if addrs.is_empty() {
+        anyhow::bail!("Host {spec} did not resolve to any addresses");
+    }
+
+    Ok(RemoteFilter::Addrs(addrs))
+}
+
+fn cidr_contains(network: IpAddr, prefix_len: u8, addr: IpAddr) -> bool {
+    match (network, addr) {
+        (IpAddr::V4(network), IpAddr::V4(addr)) => {
+            let mask = ipv4_mask(prefix_len);
+            u32::from(network) & mask == u32::from(addr) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(addr)) => {
+            let mask = ipv6_mask(prefix_len);
+            u128::from(network) & mask == u128::from(addr) & mask
+        }
+        _ => false,
+    }
+}
+
+fn ipv4_mask(prefix_len: u8) -> u32 {
+    if prefix_len == 0 {
+        0
+    } else {
+        u32::MAX << (32 - u32::from(prefix_len))
+    }
+}
+
+fn ipv6_mask(prefix_len: u8) -> u128 {
+    if prefix_len == 0 {
+        0
+    } else {
+        u128::MAX << (128 - u32::from(prefix_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipv4_cidr_matches_within_prefix() {
+        assert!(cidr_contains(
+            "10.0.0.0".parse().unwrap(),
+            8,
+            "10.255.1.1".parse().unwrap()
+        ));
+        assert!(!cidr_contains(
+            "10.0.0.0".parse().unwrap(),
+            8,
+            "11.0.0.1".parse().unwrap()
+        ));
+    }
+
+    #[test]
+    fn ipv4_prefix_zero_matches_everything() {
+        assert!(cidr_contains(
+            "0.0.0.0".parse().unwrap(),
+            0,
+            "203.0.113.7".parse().unwrap()
+        ));
+    }
+
+    #[test]
+    fn ipv4_prefix_32_matches_only_exact_address() {
+        let network = "192.168.1.42".parse().unwrap();
+        assert!(cidr_contains(network, 32, "192.168.1.42".parse().unwrap()));
+        assert!(!cidr_contains(network, 32, "192.168.1.43".parse().unwrap()));
+    }
+
+    #[test]
+    fn ipv6_cidr_matches_within_prefix() {
+        assert!(cidr_contains(
+            "fe80::".parse().unwrap(),
+            10,
+            "fe80::1".parse().unwrap()
+        ));
+        assert!(!cidr_contains(
+            "fe80::".parse().unwrap(),
+            10,
+            "fc00::1".parse().unwrap()
+        ));
+    }
+
+    #[test]
+    fn ipv6_prefix_128_matches_only_exact_address() {
+        let network = "2001:db8::1".parse().unwrap();
+        assert!(cidr_contains(network, 128, "2001:db8::1".parse().unwrap()));
+        assert!(!cidr_contains(network, 128, "2001:db8::2".parse().unwrap()));
+    }
+
+    #[test]
+    fn mixed_family_never_matches() {
+        assert!(!cidr_contains(
+            "10.0.0.0".parse().unwrap(),
+            8,
+            "::1".parse().unwrap()
+        ));
+    }
+}
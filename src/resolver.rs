@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use dns_lookup::lookup_addr;
+
+/// Maximum number of in-flight reverse-DNS lookups at any time.
+const MAX_CONCURRENT_LOOKUPS: usize = 16;
+
+/// How long to wait for the next PTR answer of a batch before giving up on
+/// the batch's remaining lookups and falling back to their numeric addresses.
+const LOOKUP_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Resolves a set of remote addresses to hostnames, caching each unique
+/// address so it's only looked up once even if it appears on many sockets.
+/// Loopback, link-local, and unspecified addresses are skipped since they
+/// never resolve usefully.
+pub fn resolve_addrs(addrs: &[IpAddr]) -> HashMap<IpAddr, Option<String>> {
+    let mut cache = HashMap::new();
+    let mut unique = Vec::new();
+
+    for &addr in addrs {
+        if is_unresolvable(addr) || cache.contains_key(&addr) {
+            continue;
+        }
+        cache.insert(addr, None);
+        unique.push(addr);
+    }
+
+    for chunk in unique.chunks(MAX_CONCURRENT_LOOKUPS) {
+        let (tx, rx) = mpsc::channel();
+
+        for &addr in chunk {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let host = lookup_addr(&addr).ok();
+                let _ = tx.send((addr, host));
+            });
+        }
+        drop(tx);
+
+        for _ in 0..chunk.len() {
+            match rx.recv_timeout(LOOKUP_TIMEOUT) {
+                Ok((addr, host)) => {
+                    cache.insert(addr, host);
+                }
+                // Timed out waiting on the next answer; every lookup in
+                // this chunk that hasn't answered yet keeps its fallback
+                // numeric address and we move on.
+                Err(_) => break,
+            }
+        }
+    }
+
+    cache
+}
+
+fn is_unresolvable(addr: IpAddr) -> bool {
+    addr.is_loopback() || addr.is_unspecified() || is_link_local(addr)
+}
+
+fn is_link_local(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => v4.is_link_local(),
+        IpAddr::V6(v6) => (v6.segments()[0] & 0xffc0) == 0xfe80,
+    }
+}
@@ -0,0 +1,429 @@
+use anyhow::Result;
+use netstat2::AddressFamilyFlags;
+use pnet::datalink::{self, Channel::Ethernet, NetworkInterface};
+use pnet::packet::Packet;
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::TcpPacket;
+use pnet::packet::udp::UdpPacket;
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessesToUpdate, System};
+use tabwriter::TabWriter;
+
+use crate::{format_command_line, get_sockets, get_protocol_string};
+
+/// Which transport a captured frame belongs to. `ProtocolFlags` from
+/// `netstat2` doesn't implement `Hash`, so we track our own tiny copy for
+/// use as a hash map key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Transport {
+    Tcp,
+    Udp,
+}
+
+/// The same 5-tuple `SocketInfo` already captures, used to key per-flow
+/// byte counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Connection {
+    protocol: Transport,
+    local_addr: IpAddr,
+    local_port: u16,
+    remote_addr: IpAddr,
+    remote_port: u16,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ByteCounters {
+    up_bytes: u64,
+    down_bytes: u64,
+}
+
+/// Picks the capture interface: the one named on the command line, or the
+/// first interface that's up, not loopback, and has an address.
+fn select_interface(name: Option<&str>) -> Result<NetworkInterface> {
+    let interfaces = datalink::interfaces();
+
+    if let Some(name) = name {
+        return interfaces
+            .into_iter()
+            .find(|iface| iface.name == name)
+            .ok_or_else(|| anyhow::anyhow!("No such network interface: {name}"));
+    }
+
+    interfaces
+        .into_iter()
+        .find(|iface| iface.is_up() && !iface.is_loopback() && !iface.ips.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("No suitable network interface found"))
+}
+
+/// Parses one Ethernet frame and, if it carries a TCP/UDP segment over
+/// IPv4/IPv6, returns the flow's 5-tuple and payload size.
+fn parse_frame(data: &[u8]) -> Option<(Connection, usize)> {
+    let ethernet = EthernetPacket::new(data)?;
+
+    match ethernet.get_ethertype() {
+        EtherTypes::Ipv4 => {
+            let ip = Ipv4Packet::new(ethernet.payload())?;
+            parse_transport(
+                IpAddr::V4(ip.get_source()),
+                IpAddr::V4(ip.get_destination()),
+                ip.get_next_level_protocol(),
+                ip.payload(),
+            )
+        }
+        EtherTypes::Ipv6 => {
+            let ip = Ipv6Packet::new(ethernet.payload())?;
+            parse_transport(
+                IpAddr::V6(ip.get_source()),
+                IpAddr::V6(ip.get_destination()),
+                ip.get_next_header(),
+                ip.payload(),
+            )
+        }
+        _ => None,
+    }
+}
+
+fn parse_transport(
+    src_ip: IpAddr,
+    dst_ip: IpAddr,
+    next_proto: pnet::packet::ip::IpNextHeaderProtocol,
+    payload: &[u8],
+) -> Option<(Connection, usize)> {
+    match next_proto {
+        IpNextHeaderProtocols::Tcp => {
+            let tcp = TcpPacket::new(payload)?;
+            Some((
+                Connection {
+                    protocol: Transport::Tcp,
+                    local_addr: src_ip,
+                    local_port: tcp.get_source(),
+                    remote_addr: dst_ip,
+                    remote_port: tcp.get_destination(),
+                },
+                tcp.payload().len(),
+            ))
+        }
+        IpNextHeaderProtocols::Udp => {
+            let udp = UdpPacket::new(payload)?;
+            Some((
+                Connection {
+                    protocol: Transport::Udp,
+                    local_addr: src_ip,
+                    local_port: udp.get_source(),
+                    remote_addr: dst_ip,
+                    remote_port: udp.get_destination(),
+                },
+                udp.payload().len(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Normalizes a captured flow against the addresses owned by the capture
+/// interface, so the same (local, remote) pair is used no matter which
+/// direction the frame travelled in.
+fn attribute(iface_ips: &[IpAddr], conn: Connection) -> Option<(Connection, bool)> {
+    if iface_ips.contains(&conn.local_addr) {
+        Some((conn, true))
+    } else if iface_ips.contains(&conn.remote_addr) {
+        Some((
+            Connection {
+                protocol: conn.protocol,
+                local_addr: conn.remote_addr,
+                local_port: conn.remote_port,
+                remote_addr: conn.local_addr,
+                remote_port: conn.local_port,
+            },
+            false,
+        ))
+    } else {
+        None
+    }
+}
+
+/// Formats a bytes-per-second rate the way the table wants it displayed.
+fn format_rate(bytes_per_sec: f64) -> String {
+    const UNITS: &[&str] = &["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut rate = bytes_per_sec;
+    let mut unit = 0;
+    while rate >= 1024.0 && unit < UNITS.len() - 1 {
+        rate /= 1024.0;
+        unit += 1;
+    }
+    format!("{rate:.1} {}", UNITS[unit])
+}
+
+/// Runs wiump as a continuous per-process bandwidth monitor: captures
+/// frames off `interface` (or an auto-selected one), attributes their
+/// bytes to the sockets `get_sockets` already enumerates, and reprints a
+/// PORT/PROCESS/UP/DOWN table every `refresh_interval`.
+pub fn run(interface: Option<&str>, refresh_interval: Duration) -> Result<()> {
+    let iface = select_interface(interface)?;
+    let iface_ips: Vec<IpAddr> = iface.ips.iter().map(|ip| ip.ip()).collect();
+
+    let counters: Arc<Mutex<HashMap<Connection, ByteCounters>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+
+    match datalink::channel(&iface, Default::default()) {
+        Ok(Ethernet(_, mut rx)) => {
+            let counters = Arc::clone(&counters);
+            thread::spawn(move || {
+                while let Ok(frame) = rx.next() {
+                    if let Some((conn, bytes)) = parse_frame(frame) {
+                        if let Some((conn, is_local_src)) = attribute(&iface_ips, conn) {
+                            let mut counters = counters.lock().unwrap();
+                            let entry = counters.entry(conn).or_default();
+                            if is_local_src {
+                                entry.up_bytes += bytes as u64;
+                            } else {
+                                entry.down_bytes += bytes as u64;
+                            }
+                        }
+                    }
+                }
+            });
+        }
+        Ok(_) => {
+            eprintln!("Warning: unsupported datalink channel type; showing zero rates.");
+        }
+        Err(e) => {
+            eprintln!(
+                "Warning: could not open capture on {} ({e}); showing zero rates. \
+                 Try running as root or with CAP_NET_RAW.",
+                iface.name
+            );
+        }
+    }
+
+    let mut sys = System::new_all();
+
+    loop {
+        let tick_start = Instant::now();
+        thread::sleep(refresh_interval);
+
+        let elapsed = tick_start.elapsed().as_secs_f64().max(f64::EPSILON);
+        let flow_counters = std::mem::take(&mut *counters.lock().unwrap());
+
+        // Aggregate per-flow counters up to per-port totals, since the
+        // table joins counters to sockets by local port, not per peer:
+        // every socket row on the same port shows that port's total.
+        // Keying on the address family too keeps a dual-stack service's
+        // separate IPv4 and IPv6 listeners (e.g. 0.0.0.0:443 and [::]:443)
+        // from sharing one counter.
+        let mut by_port: HashMap<(Transport, bool, u16), ByteCounters> = HashMap::new();
+        for (conn, bytes) in flow_counters {
+            let is_ipv6 = matches!(conn.local_addr, IpAddr::V6(_));
+            let entry = by_port
+                .entry((conn.protocol, is_ipv6, conn.local_port))
+                .or_default();
+            entry.up_bytes += bytes.up_bytes;
+            entry.down_bytes += bytes.down_bytes;
+        }
+
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        let mut sockets = get_sockets(&sys, AddressFamilyFlags::IPV4)?;
+        sockets.append(&mut get_sockets(&sys, AddressFamilyFlags::IPV6)?);
+        sockets.sort_by_key(|s| s.local_port);
+
+        let mut tw = TabWriter::new(std::io::stdout());
+        writeln!(tw, "PORT\tPROCESS\tUP\tDOWN")?;
+        for s in &sockets {
+            let transport = if s.protocol == netstat2::ProtocolFlags::UDP {
+                Transport::Udp
+            } else {
+                Transport::Tcp
+            };
+            let is_ipv6 = s.family == AddressFamilyFlags::IPV6;
+            let bytes = by_port
+                .get(&(transport, is_ipv6, s.local_port))
+                .copied()
+                .unwrap_or_default();
+            let proc_name = s
+                .processes
+                .first()
+                .map(|p| format_command_line(std::slice::from_ref(&p.name), 40))
+                .unwrap_or_else(|| "unknown".to_string());
+
+            writeln!(
+                tw,
+                "{}/{}\t{}\t{}\t{}",
+                s.local_port,
+                get_protocol_string(s.protocol, s.family),
+                proc_name,
+                format_rate(bytes.up_bytes as f64 / elapsed),
+                format_rate(bytes.down_bytes as f64 / elapsed),
+            )?;
+        }
+        tw.flush()?;
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pnet::packet::ethernet::MutableEthernetPacket;
+    use pnet::packet::ipv4::MutableIpv4Packet;
+    use pnet::packet::ipv6::MutableIpv6Packet;
+    use pnet::packet::tcp::MutableTcpPacket;
+    use pnet::packet::udp::MutableUdpPacket;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    fn ipv4_tcp_frame(
+        src: (Ipv4Addr, u16),
+        dst: (Ipv4Addr, u16),
+        payload: &[u8],
+    ) -> Vec<u8> {
+        let mut tcp_buf = vec![0u8; TcpPacket::minimum_packet_size() + payload.len()];
+        {
+            let mut tcp = MutableTcpPacket::new(&mut tcp_buf).unwrap();
+            tcp.set_source(src.1);
+            tcp.set_destination(dst.1);
+            tcp.set_data_offset(5);
+            tcp.set_payload(payload);
+        }
+
+        let ip_len = Ipv4Packet::minimum_packet_size() + tcp_buf.len();
+        let mut ip_buf = vec![0u8; ip_len];
+        {
+            let mut ip = MutableIpv4Packet::new(&mut ip_buf).unwrap();
+            ip.set_version(4);
+            ip.set_header_length(5);
+            ip.set_total_length(ip_len as u16);
+            ip.set_next_level_protocol(IpNextHeaderProtocols::Tcp);
+            ip.set_source(src.0);
+            ip.set_destination(dst.0);
+            ip.set_payload(&tcp_buf);
+        }
+
+        let mut eth_buf = vec![0u8; EthernetPacket::minimum_packet_size() + ip_buf.len()];
+        {
+            let mut eth = MutableEthernetPacket::new(&mut eth_buf).unwrap();
+            eth.set_ethertype(EtherTypes::Ipv4);
+            eth.set_payload(&ip_buf);
+        }
+
+        eth_buf
+    }
+
+    fn ipv6_udp_frame(
+        src: (Ipv6Addr, u16),
+        dst: (Ipv6Addr, u16),
+        payload: &[u8],
+    ) -> Vec<u8> {
+        let udp_len = UdpPacket::minimum_packet_size() + payload.len();
+        let mut udp_buf = vec![0u8; udp_len];
+        {
+            let mut udp = MutableUdpPacket::new(&mut udp_buf).unwrap();
+            udp.set_source(src.1);
+            udp.set_destination(dst.1);
+            udp.set_length(udp_len as u16);
+            udp.set_payload(payload);
+        }
+
+        let mut ip_buf = vec![0u8; Ipv6Packet::minimum_packet_size() + udp_buf.len()];
+        {
+            let mut ip = MutableIpv6Packet::new(&mut ip_buf).unwrap();
+            ip.set_version(6);
+            ip.set_payload_length(udp_buf.len() as u16);
+            ip.set_next_header(IpNextHeaderProtocols::Udp);
+            ip.set_source(src.0);
+            ip.set_destination(dst.0);
+            ip.set_payload(&udp_buf);
+        }
+
+        let mut eth_buf = vec![0u8; EthernetPacket::minimum_packet_size() + ip_buf.len()];
+        {
+            let mut eth = MutableEthernetPacket::new(&mut eth_buf).unwrap();
+            eth.set_ethertype(EtherTypes::Ipv6);
+            eth.set_payload(&ip_buf);
+        }
+
+        eth_buf
+    }
+
+    #[test]
+    fn parse_frame_extracts_ipv4_tcp_flow() {
+        let src = (Ipv4Addr::new(192, 168, 1, 10), 5000);
+        let dst = (Ipv4Addr::new(93, 184, 216, 34), 443);
+        let frame = ipv4_tcp_frame(src, dst, b"hello");
+
+        let (conn, bytes) = parse_frame(&frame).expect("frame should parse");
+        assert_eq!(conn.protocol, Transport::Tcp);
+        assert_eq!(conn.local_addr, IpAddr::V4(src.0));
+        assert_eq!(conn.local_port, src.1);
+        assert_eq!(conn.remote_addr, IpAddr::V4(dst.0));
+        assert_eq!(conn.remote_port, dst.1);
+        assert_eq!(bytes, 5);
+    }
+
+    #[test]
+    fn parse_frame_extracts_ipv6_udp_flow() {
+        let src = (Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1), 53000);
+        let dst = (Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2), 53);
+        let frame = ipv6_udp_frame(src, dst, b"dns-query");
+
+        let (conn, bytes) = parse_frame(&frame).expect("frame should parse");
+        assert_eq!(conn.protocol, Transport::Udp);
+        assert_eq!(conn.local_addr, IpAddr::V6(src.0));
+        assert_eq!(conn.local_port, src.1);
+        assert_eq!(conn.remote_addr, IpAddr::V6(dst.0));
+        assert_eq!(conn.remote_port, dst.1);
+        assert_eq!(bytes, 9);
+    }
+
+    fn conn(local: (IpAddr, u16), remote: (IpAddr, u16)) -> Connection {
+        Connection {
+            protocol: Transport::Tcp,
+            local_addr: local.0,
+            local_port: local.1,
+            remote_addr: remote.0,
+            remote_port: remote.1,
+        }
+    }
+
+    #[test]
+    fn attribute_marks_outbound_when_iface_owns_the_src_address() {
+        let iface_ip: IpAddr = "192.168.1.10".parse().unwrap();
+        let peer: IpAddr = "93.184.216.34".parse().unwrap();
+        let c = conn((iface_ip, 5000), (peer, 443));
+
+        let (attributed, is_outbound) = attribute(&[iface_ip], c).expect("should attribute");
+        assert!(is_outbound);
+        assert_eq!(attributed.local_addr, iface_ip);
+        assert_eq!(attributed.remote_addr, peer);
+    }
+
+    #[test]
+    fn attribute_swaps_and_marks_inbound_when_iface_owns_the_dst_address() {
+        let iface_ip: IpAddr = "192.168.1.10".parse().unwrap();
+        let peer: IpAddr = "93.184.216.34".parse().unwrap();
+        // Captured as (src=peer, dst=iface) -- e.g. a reply frame.
+        let c = conn((peer, 443), (iface_ip, 5000));
+
+        let (attributed, is_outbound) = attribute(&[iface_ip], c).expect("should attribute");
+        assert!(!is_outbound);
+        assert_eq!(attributed.local_addr, iface_ip);
+        assert_eq!(attributed.local_port, 5000);
+        assert_eq!(attributed.remote_addr, peer);
+        assert_eq!(attributed.remote_port, 443);
+    }
+
+    #[test]
+    fn attribute_returns_none_for_unrelated_traffic() {
+        let iface_ip: IpAddr = "192.168.1.10".parse().unwrap();
+        let a: IpAddr = "10.0.0.1".parse().unwrap();
+        let b: IpAddr = "10.0.0.2".parse().unwrap();
+
+        assert!(attribute(&[iface_ip], conn((a, 1234), (b, 443))).is_none());
+    }
+}